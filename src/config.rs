@@ -0,0 +1,168 @@
+//! Optional user configuration, read from `$XDG_CONFIG_HOME/checkout-ago/config`
+//! (or `~/.config/checkout-ago/config`).
+//!
+//! The format is a small INI subset:
+//!
+//! ```text
+//! [aliases]
+//! sprint = "2 weeks"
+//! q = 3mo
+//!
+//! [checkout]
+//! allow-ref = main
+//! allow-ref = refs/heads/release/*
+//! ```
+
+use crate::error::AgoError;
+use crate::messages;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// User-defined time words, e.g. `sprint` -> `2 weeks`.
+    pub aliases: BTreeMap<String, String>,
+    /// Refs (or ref globs) the target must be reachable from; see `--allow-ref`.
+    pub allowed_refs: Vec<String>,
+}
+
+impl Config {
+    /// Location of the config file, if a home or config directory is known.
+    pub fn path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(base.join("checkout-ago").join("config"))
+    }
+
+    /// Read the config file. A missing file yields the default config.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut config = Self::default();
+        let mut section = String::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(index, line));
+            };
+            let (key, value) = (key.trim(), unquote(value.trim()));
+
+            if section == "aliases" {
+                if key.is_empty() || key.starts_with(|c: char| c.is_ascii_digit() || c == '@') {
+                    return Err(AgoError::InvalidConfig(messages::invalid_alias_name(key)).into());
+                }
+                config.aliases.insert(key.to_string(), value.to_string());
+            } else if section == "checkout" && key == "allow-ref" {
+                config.allowed_refs.push(value.to_string());
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Replace `input` with its alias expansion, if it is an alias.
+    pub fn expand_alias(&self, input: &str) -> String {
+        self.aliases
+            .get(input.trim())
+            .cloned()
+            .unwrap_or_else(|| input.to_string())
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn invalid(index: usize, line: &str) -> Box<dyn Error> {
+    AgoError::InvalidConfig(messages::invalid_config_line(index + 1, line)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::normalize_ago;
+
+    const SAMPLE: &str = "# my shortcuts\n\
+                          [aliases]\n\
+                          sprint = \"2 weeks\"\n\
+                          q = 12w\n\
+                          \n\
+                          [checkout]\n\
+                          allow-ref = main\n\
+                          allow-ref = refs/heads/release/*\n\
+                          [other]\n\
+                          ignored = yes\n";
+
+    #[test]
+    fn test_parse_aliases() {
+        let config = Config::parse(SAMPLE).unwrap();
+
+        assert_eq!(config.aliases.len(), 2);
+        assert_eq!(config.aliases["sprint"], "2 weeks");
+        assert_eq!(config.aliases["q"], "12w");
+        assert_eq!(config.allowed_refs, ["main", "refs/heads/release/*"]);
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let config = Config::parse(SAMPLE).unwrap();
+
+        assert_eq!(config.expand_alias("sprint"), "2 weeks");
+        assert_eq!(config.expand_alias("3d"), "3d");
+    }
+
+    #[test]
+    fn test_alias_expansion_feeds_shorthand() {
+        let config = Config::parse(SAMPLE).unwrap();
+
+        assert_eq!(normalize_ago(&config.expand_alias("q")), "12 weeks");
+        assert_eq!(normalize_ago(&config.expand_alias("2d")), "2 days");
+    }
+
+    #[test]
+    fn test_aliases_cannot_shadow_numeric_shorthand() {
+        let err = Config::parse("[aliases]\n2d = 1 week\n").unwrap_err();
+        assert_eq!(err.to_string(), messages::invalid_alias_name("2d"));
+        assert!(Config::parse("[aliases]\n@1 = 1 week\n").is_err());
+    }
+
+    #[test]
+    fn test_malformed_line() {
+        let err = Config::parse("[aliases]\nsprint\n").unwrap_err();
+        assert_eq!(err.to_string(), messages::invalid_config_line(2, "sprint"));
+    }
+}
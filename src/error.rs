@@ -0,0 +1,127 @@
+use crate::json;
+use std::error::Error;
+use std::fmt;
+
+/// Failures the tool reports to users, grouped so scripts can tell them apart.
+/// Each variant carries its user-facing message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgoError {
+    /// A git command failed.
+    GitFailed(String),
+    /// No commit matched the request.
+    NoCommitFound(String),
+    /// The time expression couldn't be understood.
+    InvalidTime(String),
+    /// A ref or object named by the user or resolved by git isn't usable.
+    InvalidRef(String),
+    /// The checkout was refused because of the state of the working tree.
+    CheckoutBlocked(String),
+    /// The config file couldn't be understood.
+    InvalidConfig(String),
+    /// The directory given isn't inside a git repository.
+    NoRepository(String),
+    /// The remote isn't hosted somewhere the tool knows how to link to.
+    UnsupportedRemote(String),
+}
+
+impl AgoError {
+    /// Stable identifier for this kind of failure.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::GitFailed(_) => "git_failed",
+            Self::NoCommitFound(_) => "no_commit_found",
+            Self::InvalidTime(_) => "invalid_time",
+            Self::InvalidRef(_) => "invalid_ref",
+            Self::CheckoutBlocked(_) => "checkout_blocked",
+            Self::InvalidConfig(_) => "invalid_config",
+            Self::NoRepository(_) => "no_repository",
+            Self::UnsupportedRemote(_) => "unsupported_remote",
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::GitFailed(message)
+            | Self::NoCommitFound(message)
+            | Self::InvalidTime(message)
+            | Self::InvalidRef(message)
+            | Self::CheckoutBlocked(message)
+            | Self::InvalidConfig(message)
+            | Self::NoRepository(message)
+            | Self::UnsupportedRemote(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for AgoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl Error for AgoError {}
+
+/// Render any error as `{"error":"<code>","message":"..."}`. Errors that
+/// aren't an [`AgoError`] (e.g. I/O failures) use the code `unexpected`.
+pub fn to_json(error: &(dyn Error + 'static)) -> String {
+    let code = error
+        .downcast_ref::<AgoError>()
+        .map_or("unexpected", AgoError::code);
+
+    json::object(&[
+        ("error", json::string(code)),
+        ("message", json::string(&error.to_string())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_variants_serialize_to_json_shape() {
+        let cases = [
+            (
+                AgoError::GitFailed("git rev-list failed".into()),
+                "git_failed",
+            ),
+            (AgoError::NoCommitFound("none".into()), "no_commit_found"),
+            (AgoError::InvalidTime("bad".into()), "invalid_time"),
+            (AgoError::InvalidRef("nope".into()), "invalid_ref"),
+            (
+                AgoError::CheckoutBlocked("dirty".into()),
+                "checkout_blocked",
+            ),
+            (AgoError::InvalidConfig("bad key".into()), "invalid_config"),
+            (AgoError::NoRepository("nowhere".into()), "no_repository"),
+            (
+                AgoError::UnsupportedRemote("example.com".into()),
+                "unsupported_remote",
+            ),
+        ];
+
+        for (error, code) in cases {
+            let message = error.to_string();
+            assert_eq!(
+                to_json(&error),
+                format!("{{\"error\":\"{code}\",\"message\":\"{message}\"}}")
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_errors_are_unexpected() {
+        let error = io::Error::other("disk on fire");
+        assert_eq!(
+            to_json(&error),
+            "{\"error\":\"unexpected\",\"message\":\"disk on fire\"}"
+        );
+    }
+
+    #[test]
+    fn test_boxed_errors_keep_their_code() {
+        let boxed: Box<dyn Error> = AgoError::NoCommitFound("none".into()).into();
+        assert!(to_json(boxed.as_ref()).contains("\"no_commit_found\""));
+    }
+}
@@ -0,0 +1,86 @@
+//! Minimal JSON rendering for machine-readable output.
+
+use std::fmt::Write;
+
+/// Render `value` as a JSON string literal.
+pub fn string(value: &str) -> String {
+    let mut rendered = String::with_capacity(value.len() + 2);
+    rendered.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => rendered.push_str("\\\""),
+            '\\' => rendered.push_str("\\\\"),
+            '\n' => rendered.push_str("\\n"),
+            '\r' => rendered.push_str("\\r"),
+            '\t' => rendered.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(rendered, "\\u{:04x}", u32::from(c));
+            }
+            c => rendered.push(c),
+        }
+    }
+
+    rendered.push('"');
+    rendered
+}
+
+/// Render an optional string, using `null` for `None`.
+pub fn optional_string(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_string(), string)
+}
+
+/// Render an optional integer, using `null` for `None`.
+pub fn optional_integer(value: Option<i64>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+/// Render an array of strings.
+pub fn string_array(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| string(value)).collect();
+
+    format!("[{}]", items.join(","))
+}
+
+/// Render an object from keys and already-rendered JSON values.
+pub fn object(fields: &[(&str, String)]) -> String {
+    let body: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{value}", string(key)))
+        .collect();
+
+    format!("{{{}}}", body.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(string("plain"), "\"plain\"");
+        assert_eq!(string("a \"b\"\\c\nd"), "\"a \\\"b\\\"\\\\c\\nd\"");
+        assert_eq!(string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_string_array() {
+        assert_eq!(string_array(&[]), "[]");
+        assert_eq!(
+            string_array(&["a".to_string(), "b \"c\"".to_string()]),
+            "[\"a\",\"b \\\"c\\\"\"]"
+        );
+    }
+
+    #[test]
+    fn test_object() {
+        assert_eq!(
+            object(&[
+                ("a", string("x")),
+                ("b", "true".into()),
+                ("c", optional_string(None))
+            ]),
+            "{\"a\":\"x\",\"b\":true,\"c\":null}"
+        );
+    }
+}
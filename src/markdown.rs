@@ -0,0 +1,89 @@
+//! GitHub-flavoured Markdown rendering for output meant to be pasted into
+//! issues and pull requests.
+
+/// Escape a value for use inside a table cell.
+pub fn cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('\n', " ")
+}
+
+/// Render a table with columns padded to line up in plain text too.
+/// Cells are escaped here, so callers pass raw values.
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let headers: Vec<String> = headers.iter().map(|header| cell(header)).collect();
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|value| cell(value)).collect())
+        .collect();
+
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .chain([&headers[column]])
+                .map(|value| value.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(column, width)| {
+                let value = cells.get(column).map_or("", String::as_str);
+                format!("{value}{}", " ".repeat(width - value.chars().count()))
+            })
+            .collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+    let mut rendered = line(&headers);
+    rendered.push_str(&line(&separator));
+    for row in &rows {
+        rendered.push_str(&line(row));
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_escapes_pipes() {
+        assert_eq!(cell("a | b"), "a \\| b");
+        assert_eq!(cell("line\nbreak"), "line break");
+    }
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let rows = vec![
+            vec![
+                "2d".to_string(),
+                "abc1234".to_string(),
+                "Fix | parsing".to_string(),
+            ],
+            vec![
+                "1 week".to_string(),
+                "def5678".to_string(),
+                "Init".to_string(),
+            ],
+        ];
+
+        assert_eq!(
+            table(&["Time", "Commit", "Subject"], &rows),
+            "| Time   | Commit  | Subject        |\n\
+             | ------ | ------- | -------------- |\n\
+             | 2d     | abc1234 | Fix \\| parsing |\n\
+             | 1 week | def5678 | Init           |\n"
+        );
+    }
+}
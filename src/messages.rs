@@ -0,0 +1,329 @@
+//! User-facing messages, kept in one place so the wording stays consistent
+//! and could later be translated.
+
+pub const ERROR_PREFIX: &str = "error";
+
+pub const REV_PARSE_FAILED: &str = "git rev-parse failed";
+pub const REV_LIST_FAILED: &str = "git rev-list failed";
+pub const FOR_EACH_REF_FAILED: &str = "git for-each-ref failed";
+pub const LOG_FAILED: &str = "git log failed";
+pub const CLONE_FAILED: &str = "git clone failed";
+pub const DIFF_FAILED: &str = "git diff failed";
+pub const CHECKOUT_FAILED: &str = "git checkout failed";
+pub const SHOW_FAILED: &str = "git show failed";
+pub const STATUS_FAILED: &str = "git status failed";
+pub const LS_FILES_FAILED: &str = "git ls-files failed";
+pub const SPARSE_REAPPLY_FAILED: &str = "git sparse-checkout reapply failed";
+pub const NO_COMMIT_FOUND: &str = "no commit found before the given time";
+pub const NO_COMMIT_IN_SHALLOW: &str = "no commit found before the given time in this shallow \
+     clone; older history may not be fetched yet (try: git fetch --unshallow)";
+pub const NO_TAGS: &str = "no tags found in the history of HEAD";
+pub const NO_UPSTREAM: &str =
+    "the current branch has no upstream; set one with: git branch --set-upstream-to";
+pub const NO_COMMITS: &str = "no commits found in the history of HEAD";
+pub const NO_COMMIT_NEAR: &str = "no commits found on either side of the given time";
+pub const NO_COMMIT_IN_RANGE: &str = "no commits found between the given times";
+pub const INVALID_ENV_PREFIX: &str = "expected letters, digits and '_', not starting with a digit";
+pub const INVALID_STASH_REF: &str = "expected a stash entry like stash@{2}, or refs/stash";
+pub const PATCH_TO_STDOUT_WITH_JSON: &str =
+    "--patch-out - cannot be used with --json, which keeps stdout for the outcome";
+
+pub const CURRENT_HEAD: &str = "Current HEAD";
+pub const TARGET_COMMIT: &str = "Target commit";
+pub const AUTHOR_DATE: &str = "Author date";
+pub const COMMITTER_DATE: &str = "Committer date";
+pub const COMMIT_URL: &str = "Commit URL";
+pub const TO_RETURN: &str = "To return";
+pub const TEMP_CLONE: &str = "Temporary clone";
+pub const TO_REMOVE: &str = "To remove";
+pub const BEFORE_CUTOFF: &str = "Commit before cutoff";
+pub const ALREADY_AT_HEAD: &str = "already at HEAD; nothing to do";
+pub const REPEATED_JUMP: &str = "HEAD is still where the last identical run left it; nothing to do \
+     (use --no-idempotent to resolve TIME again)";
+pub const RETURNED_TO: &str = "Returned to";
+pub const SPARSE_HINT: &str = "hint: this is a sparse checkout; rerun with --respect-sparse to reapply \
+     the sparse patterns after the jump";
+pub const WROTE: &str = "Wrote";
+pub const ALIASES: &str = "Aliases";
+pub const REPORT: &str = "Report";
+pub const AGE: &str = "Age";
+pub const BEHIND_HEAD: &str = "Commits behind HEAD";
+pub const WORKING_TREE: &str = "Working tree";
+pub const CLEAN: &str = "clean";
+pub const DIRTY: &str = "has uncommitted changes";
+pub const CHANGES_TO_HEAD: &str = "Changes from target to HEAD";
+pub const GIT_NOT_FOUND: &str = "git could not be run; is it installed and on PATH?";
+pub const NOT_IN_REPOSITORY: &str = "not inside a git repository";
+pub const TREE_CLEAN: &str = "working tree is clean";
+pub const TREE_DIRTY: &str =
+    "working tree has uncommitted changes; checkout may refuse or carry them along";
+pub const CHECK_OK: &str = "ok";
+pub const CHECK_WARN: &str = "warn";
+pub const CHECK_FAIL: &str = "fail";
+pub const HEAD_DETACHED: &str = "HEAD is detached";
+pub const NO_OPERATION: &str = "no merge, rebase, cherry-pick or revert in progress";
+pub const SKIPPED_COMMITS: &str = "Commits between target and HEAD";
+
+pub fn missing_ago(env_var: &str) -> String {
+    format!("no TIME given; pass one (e.g. 2d) or set {env_var}")
+}
+
+pub fn invalid_config_line(line_number: usize, line: &str) -> String {
+    format!("config line {line_number}: expected 'key = value', got '{line}'")
+}
+
+pub fn invalid_alias_name(name: &str) -> String {
+    format!(
+        "alias '{name}' would shadow numeric shorthand; alias names must not start with a digit or '@'"
+    )
+}
+
+pub fn ambiguous_unit(unit: &str) -> String {
+    format!("ambiguous unit '{unit}'; use 'min' for minutes or 'mo' for months")
+}
+
+pub fn invalid_iso_week(input: &str) -> String {
+    format!("invalid ISO week '{input}': expected YYYY-Www, e.g. 2024-W07")
+}
+
+pub fn invalid_holiday(line_number: usize, line: &str) -> String {
+    format!("holidays line {line_number}: expected a date like 2024-12-25, got '{line}'")
+}
+
+pub fn unreadable_holidays(path: &str, detail: &str) -> String {
+    format!("could not read holidays file {path}: {detail}")
+}
+
+pub fn invalid_git_config(key: &str, value: &str) -> String {
+    format!("git config {key} has an invalid value '{value}'")
+}
+
+pub fn alias_installed(name: &str, local: bool) -> String {
+    let scope = if local {
+        "this repository's"
+    } else {
+        "your global"
+    };
+    format!("Added alias '{name}' to {scope} git config; try: git {name} 2d")
+}
+
+pub fn config_write_failed(detail: &str) -> String {
+    format!("could not write git config: {detail}")
+}
+
+pub fn invalid_epoch(input: &str) -> String {
+    format!("invalid epoch timestamp '{input}': expected @<seconds>")
+}
+
+pub fn reflog_failed(detail: &str) -> String {
+    format!("could not resolve HEAD from the reflog: {detail}")
+}
+
+pub fn no_timestamp(input: &str) -> String {
+    format!("cannot compute a timestamp for '{input}'; use a form like 2d, '3 weeks' or @<seconds>")
+}
+
+pub fn no_repository(path: &str) -> String {
+    format!("no git repository found at or above {path}")
+}
+
+pub fn subcommand_conflict(arg: &str) -> String {
+    format!("the argument '{arg}' cannot be used with a subcommand")
+}
+
+pub fn unknown_remote(remote: &str) -> String {
+    format!("no remote named '{remote}'; see: git remote -v")
+}
+
+pub fn unsupported_remote_url(remote: &str, url: &str) -> String {
+    format!(
+        "cannot link to commits for remote '{remote}' ({url}); --url supports github.com and gitlab.com"
+    )
+}
+
+pub fn remote_head_unset(remote: &str) -> String {
+    format!(
+        "the default branch of '{remote}' isn't known locally; set it with: git remote set-head {remote} --auto"
+    )
+}
+
+pub fn unknown_stash(stash: &str) -> String {
+    format!("no stash entry '{stash}'; see: git stash list")
+}
+
+pub fn unknown_tag(tag: &str) -> String {
+    format!("no tag named '{tag}'")
+}
+
+pub fn no_commit_since_tag(tag: &str) -> String {
+    format!("no commits found between {tag} and the given time")
+}
+
+pub fn absolute_with_anchor(input: &str) -> String {
+    format!(
+        "'{input}' is a point in time, so --anchor has nothing to count back from; use an offset like 2d"
+    )
+}
+
+pub fn tags_ago_conflict(option: &str) -> String {
+    format!(
+        "counting back in tags (e.g. 3t) goes by the tag list, so it can't be combined with {option}"
+    )
+}
+
+pub fn unknown_ref(reference: &str) -> String {
+    format!("'{reference}' does not name a commit")
+}
+
+pub fn not_allowed(target: &str, allowed: &[String]) -> String {
+    format!(
+        "refusing to check out {target}: it isn't reachable from any allowed ref ({})",
+        allowed.join(", ")
+    )
+}
+
+pub fn reproduce_forward(target: &str) -> String {
+    format!("jump to {target}")
+}
+
+pub fn reproduce_return(return_ref: &str, original_head: &str) -> String {
+    if return_ref == original_head {
+        format!("return to {original_head}")
+    } else {
+        format!("return to {return_ref} (at {original_head} when this was printed)")
+    }
+}
+
+pub fn in_repository(toplevel: &str) -> String {
+    format!("inside repository {toplevel}")
+}
+
+pub fn on_branch(branch: &str) -> String {
+    format!("on branch {branch}")
+}
+
+pub fn operation_active(operation: &str) -> String {
+    format!("a {operation} is in progress; finish or abort it first")
+}
+
+pub fn operation_in_progress(operation: &str) -> String {
+    format!(
+        "a {operation} is in progress; moving HEAD now would abandon it\n\
+         finish or abort it first (git {operation} --continue or --abort), or rerun with --force"
+    )
+}
+
+pub fn head_moved_before_failure(target: &str) -> String {
+    format!(
+        "hint: HEAD had already moved to {target} when the checkout failed; a checkout hook may not have finished"
+    )
+}
+
+pub fn temp_ref_left(detail: &str, reference: &str) -> String {
+    format!("warning: {detail}; remove the leftover ref with: git update-ref -d {reference}")
+}
+
+pub fn git_timed_out(command: &str, seconds: f64) -> String {
+    format!("git {command} timed out after {seconds}s")
+}
+
+pub fn update_ref_failed(detail: &str) -> String {
+    format!("git update-ref failed: {detail}")
+}
+
+pub fn compared_to(reference: &str) -> String {
+    format!("Changes from {reference} to target")
+}
+
+pub fn invalid_step(input: &str) -> String {
+    format!("invalid --step '{input}': expected a positive duration like 1w or '3 days'")
+}
+
+pub fn step_result(step: usize, sha: &str, point: i64, status: Option<i32>) -> String {
+    match status {
+        Some(code) => format!("Step {step}: {sha} (@{point}): exit {code}"),
+        None => format!("Step {step}: {sha} (@{point}): killed by a signal"),
+    }
+}
+
+pub fn step_without_commit(step: usize, point: i64) -> String {
+    format!("Step {step}: no commit before @{point}; stopping")
+}
+
+pub fn restore_failed(reference: &str, quoted: &str, detail: &str) -> String {
+    format!("could not check {reference} out again: {detail}\nrun: git checkout {quoted}")
+}
+
+pub fn nearest_side(side: &str, delta: &str) -> String {
+    format!("Nearest commit: {delta} {side} the cutoff")
+}
+
+pub fn processed(done: usize, total: usize) -> String {
+    format!("processed {done}/{total}")
+}
+
+pub fn only_empty_commits(examined: usize) -> String {
+    format!("the {examined} most recent commits before the given time don't change any files")
+}
+
+pub fn not_enough_tags(back: usize, available: usize) -> String {
+    format!("cannot go back {back} tags: the repository has {available}")
+}
+
+pub fn object_unreadable(sha: &str) -> String {
+    format!("git object {sha} is missing or unreadable")
+}
+
+pub fn not_a_commit(sha: &str, kind: &str) -> String {
+    format!("target {sha} is a {kind}, not a commit")
+}
+
+pub fn submodules_out_of_sync(paths: &[String]) -> String {
+    format!(
+        "hint: submodules out of sync with the checked-out commit: {}\n\
+         hint: rerun with --submodules, or run: git submodule update --init --recursive",
+        paths.join(", ")
+    )
+}
+
+pub fn no_common_ancestor(commit: &str, other: &str) -> String {
+    format!("{commit} and {other} have no common ancestor")
+}
+
+pub fn merge_base_failed(detail: &str) -> String {
+    format!("git merge-base failed: {detail}")
+}
+
+pub fn worktree_stale(path: &str) -> String {
+    format!(
+        "git still has a worktree registered at {path}, though the directory is gone\n\
+         clear stale entries with: git worktree prune\n\
+         or rerun with --worktree-force to reuse it"
+    )
+}
+
+pub fn worktree_locked(path: &str, quoted: &str) -> String {
+    format!(
+        "the worktree registered at {path} is locked\n\
+         unlock and clear it with: git worktree unlock {quoted} && git worktree prune"
+    )
+}
+
+pub fn worktree_branch_in_use(branch: &str, other: &str) -> String {
+    format!(
+        "branch {branch} is already checked out in the worktree at {other}\n\
+         switch that worktree to another branch, or remove it, first"
+    )
+}
+
+pub fn worktree_path_exists(path: &str) -> String {
+    format!("{path} already exists; choose another --worktree path or remove it first")
+}
+
+pub fn untracked_would_be_overwritten(files: &[String]) -> String {
+    format!(
+        "checkout would overwrite untracked files:\n    {}\n\
+         move or delete them, or stash them with: git stash --include-untracked",
+        files.join("\n    ")
+    )
+}
@@ -0,0 +1,661 @@
+use crate::error::AgoError;
+use crate::messages;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::mem;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Captured result of a single git invocation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Extra environment variables for a git invocation, e.g. `GIT_REFLOG_ACTION`.
+pub type GitEnv<'a> = &'a [(&'a str, &'a str)];
+
+/// Runs git commands. Abstracted so the core logic can be tested without a repository.
+pub trait GitRunner {
+    fn git_with_env(&self, args: &[String], env: GitEnv) -> Result<GitOutput, Box<dyn Error>>;
+
+    fn git(&self, args: &[String]) -> Result<GitOutput, Box<dyn Error>> {
+        self.git_with_env(args, &[])
+    }
+
+    /// Run git, handing each line of stdout to `on_line` as it is read, so long
+    /// listings are never held in memory at once. The returned output has an
+    /// empty `stdout`. By default the output is buffered and then split.
+    fn git_lines(&self, args: &[String], on_line: LineSink) -> Result<GitOutput, Box<dyn Error>> {
+        let mut output = self.git(args)?;
+        for_each_line(output.stdout.as_bytes(), on_line)?;
+        output.stdout.clear();
+        Ok(output)
+    }
+
+    /// Run git, returning its stdout as raw bytes for output that needn't be
+    /// UTF-8, like patches. The returned output has an empty `stdout`. By
+    /// default the output is captured as text and handed back as bytes.
+    fn git_bytes(&self, args: &[String]) -> Result<(GitOutput, Vec<u8>), Box<dyn Error>> {
+        let mut output = self.git(args)?;
+        let stdout = mem::take(&mut output.stdout).into_bytes();
+        Ok((output, stdout))
+    }
+}
+
+/// Callback receiving streamed lines of git output, without the line ending.
+pub type LineSink<'a> = &'a mut dyn FnMut(&str) -> Result<(), Box<dyn Error>>;
+
+/// Returned from a [`LineSink`] that has seen all it needs. git is stopped
+/// and the command counts as a success, without reading the rest.
+#[derive(Debug)]
+pub struct StopReading;
+
+impl fmt::Display for StopReading {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("stopped reading git output")
+    }
+}
+
+impl Error for StopReading {}
+
+/// Feed every line of `reader` to `on_line`, until it returns [`StopReading`].
+pub fn for_each_line(reader: impl BufRead, on_line: LineSink) -> Result<(), Box<dyn Error>> {
+    for line in reader.lines() {
+        match on_line(&line?) {
+            Err(e) if e.is::<StopReading>() => break,
+            result => result?,
+        }
+    }
+
+    Ok(())
+}
+
+/// How often a command with a timeout is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How many lines of streamed output may be read ahead of the consumer.
+const LINE_BUFFER: usize = 1024;
+
+/// Runs the `git` binary found on `PATH`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemGit {
+    /// Kill any git command still running after this long.
+    pub timeout: Option<Duration>,
+}
+
+impl SystemGit {
+    fn spawn(args: &[String], env: GitEnv) -> io::Result<Child> {
+        Command::new("git")
+            .args(args)
+            .envs(env.iter().copied())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+
+    /// Run git to completion, returning its output with stdout left as bytes.
+    fn capture(
+        &self,
+        args: &[String],
+        env: GitEnv,
+    ) -> Result<(GitOutput, Vec<u8>), Box<dyn Error>> {
+        let start = Instant::now();
+        let mut child = Self::spawn(args, env)?;
+
+        // Drain both pipes on their own threads so a chatty git can't block on a full one.
+        let stdout = drain(child.stdout.take().ok_or("git stdout was not captured")?);
+        let stderr = drain(child.stderr.take().ok_or("git stderr was not captured")?);
+
+        let status = wait_with_timeout(&mut child, args, start, self.timeout)?;
+        let output = GitOutput {
+            success: status.success(),
+            stdout: String::new(),
+            stderr: String::from_utf8_lossy(&joined(stderr)?).into_owned(),
+        };
+
+        Ok((output, joined(stdout)?))
+    }
+}
+
+impl GitRunner for SystemGit {
+    fn git_with_env(&self, args: &[String], env: GitEnv) -> Result<GitOutput, Box<dyn Error>> {
+        let (output, stdout) = self.capture(args, env)?;
+
+        Ok(GitOutput {
+            stdout: String::from_utf8(stdout)?,
+            ..output
+        })
+    }
+
+    fn git_bytes(&self, args: &[String]) -> Result<(GitOutput, Vec<u8>), Box<dyn Error>> {
+        self.capture(args, &[])
+    }
+
+    fn git_lines(&self, args: &[String], on_line: LineSink) -> Result<GitOutput, Box<dyn Error>> {
+        let start = Instant::now();
+        let mut child = Self::spawn(args, &[])?;
+        let stderr = drain(child.stderr.take().ok_or("git stderr was not captured")?);
+
+        // Lines are read on another thread so the timeout can fire while git is silent.
+        let stdout = child.stdout.take().ok_or("git stdout was not captured")?;
+        let (send, lines) = mpsc::sync_channel(LINE_BUFFER);
+        let reader = thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if send.send(line?).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        let deadline = self.timeout.map(|timeout| start + timeout);
+        let streamed = loop {
+            let line = match deadline {
+                Some(deadline) => {
+                    lines.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => lines.recv().map_err(RecvTimeoutError::from),
+            };
+
+            match line {
+                Ok(line) => {
+                    if let Err(e) = on_line(&line) {
+                        break Err(e);
+                    }
+                }
+                // Either git closed stdout or, out of time, waiting below kills it.
+                Err(RecvTimeoutError::Disconnected | RecvTimeoutError::Timeout) => break Ok(()),
+            }
+        };
+        drop(lines);
+
+        let stopped = matches!(&streamed, Err(e) if e.is::<StopReading>());
+        if streamed.is_err() {
+            // Stop git instead of waiting for output nobody will read.
+            child.kill().ok();
+        }
+
+        let status = wait_with_timeout(&mut child, args, start, self.timeout)?;
+        joined(reader)?;
+        let stderr = joined(stderr)?;
+        if !stopped {
+            streamed?;
+        }
+
+        Ok(GitOutput {
+            success: stopped || status.success(),
+            stdout: String::new(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        })
+    }
+}
+
+/// Read `pipe` to the end on its own thread.
+fn drain(mut pipe: impl Read + Send + 'static) -> JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        pipe.read_to_end(&mut buffer).map(|_| buffer)
+    })
+}
+
+fn joined<T>(reader: JoinHandle<io::Result<T>>) -> Result<T, Box<dyn Error>> {
+    Ok(reader.join().map_err(|_| "git output reader panicked")??)
+}
+
+/// Wait for `child` to exit. With a `timeout`, it is killed once that long has
+/// passed since `start`, and reaped so it is neither left running nor a zombie.
+fn wait_with_timeout(
+    child: &mut Child,
+    args: &[String],
+    start: Instant,
+    timeout: Option<Duration>,
+) -> Result<ExitStatus, Box<dyn Error>> {
+    let Some(timeout) = timeout else {
+        return Ok(child.wait()?);
+    };
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            // It may have exited since try_wait; wait() below reaps it either way.
+            child.kill().ok();
+            child.wait()?;
+            return Err(AgoError::GitFailed(messages::git_timed_out(
+                subcommand(args),
+                timeout.as_secs_f64(),
+            ))
+            .into());
+        }
+
+        thread::sleep(POLL_INTERVAL.min(timeout.saturating_sub(elapsed)));
+    }
+}
+
+/// Wraps another runner and reports how long each git command took on stderr.
+pub struct TimedRunner<'a> {
+    inner: &'a dyn GitRunner,
+}
+
+impl<'a> TimedRunner<'a> {
+    pub fn new(inner: &'a dyn GitRunner) -> Self {
+        Self { inner }
+    }
+}
+
+impl GitRunner for TimedRunner<'_> {
+    fn git_with_env(&self, args: &[String], env: GitEnv) -> Result<GitOutput, Box<dyn Error>> {
+        let start = Instant::now();
+        let result = self.inner.git_with_env(args, env);
+        eprintln!("{}", format_timing(subcommand(args), start.elapsed()));
+        result
+    }
+
+    fn git_lines(&self, args: &[String], on_line: LineSink) -> Result<GitOutput, Box<dyn Error>> {
+        let start = Instant::now();
+        let result = self.inner.git_lines(args, on_line);
+        eprintln!("{}", format_timing(subcommand(args), start.elapsed()));
+        result
+    }
+
+    fn git_bytes(&self, args: &[String]) -> Result<(GitOutput, Vec<u8>), Box<dyn Error>> {
+        let start = Instant::now();
+        let result = self.inner.git_bytes(args);
+        eprintln!("{}", format_timing(subcommand(args), start.elapsed()));
+        result
+    }
+}
+
+/// Wraps another runner, running every command in `dir` via `git -C`.
+pub struct InDir<'a> {
+    inner: &'a dyn GitRunner,
+    dir: String,
+}
+
+impl<'a> InDir<'a> {
+    pub fn new(inner: &'a dyn GitRunner, dir: &Path) -> Self {
+        Self {
+            inner,
+            dir: dir.display().to_string(),
+        }
+    }
+
+    fn with_dir(&self, args: &[String]) -> Vec<String> {
+        let mut full = vec!["-C".to_string(), self.dir.clone()];
+        full.extend(args.iter().cloned());
+        full
+    }
+}
+
+impl GitRunner for InDir<'_> {
+    fn git_with_env(&self, args: &[String], env: GitEnv) -> Result<GitOutput, Box<dyn Error>> {
+        self.inner.git_with_env(&self.with_dir(args), env)
+    }
+
+    fn git_lines(&self, args: &[String], on_line: LineSink) -> Result<GitOutput, Box<dyn Error>> {
+        self.inner.git_lines(&self.with_dir(args), on_line)
+    }
+
+    fn git_bytes(&self, args: &[String]) -> Result<(GitOutput, Vec<u8>), Box<dyn Error>> {
+        self.inner.git_bytes(&self.with_dir(args))
+    }
+}
+
+/// The git subcommand in an argument list, skipping global options like `-C <dir>`.
+fn subcommand(args: &[String]) -> &str {
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-C" | "-c" => {
+                iter.next();
+            }
+            flag if flag.starts_with('-') => {}
+            name => return name,
+        }
+    }
+
+    "git"
+}
+
+/// Render a timing line such as `rev-list: 1.82s`.
+pub fn format_timing(label: &str, elapsed: Duration) -> String {
+    format!("{label}: {:.2}s", elapsed.as_secs_f64())
+}
+
+/// Build an owned argument vector from string literals.
+pub fn to_args(parts: &[&str]) -> Vec<String> {
+    parts.iter().map(|part| (*part).to_string()).collect()
+}
+
+/// Whether git failed because another process holds a lock file (e.g. `index.lock`).
+pub fn is_lock_error(stderr: &str) -> bool {
+    stderr.contains(".lock': File exists") || stderr.contains("index.lock")
+}
+
+/// Run a git command, retrying with exponential backoff while it fails due to a
+/// held lock file. Any other failure is returned immediately.
+pub fn run_with_lock_retries(
+    runner: &dyn GitRunner,
+    args: &[String],
+    env: GitEnv,
+    retries: u32,
+    base_delay: Duration,
+) -> Result<GitOutput, Box<dyn Error>> {
+    let mut attempt = 0;
+
+    loop {
+        let output = runner.git_with_env(args, env)?;
+
+        if output.success || attempt >= retries || !is_lock_error(&output.stderr) {
+            return Ok(output);
+        }
+
+        thread::sleep(base_delay * 2u32.saturating_pow(attempt));
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::{GitEnv, GitOutput, GitRunner};
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::error::Error;
+
+    impl GitOutput {
+        pub fn ok(stdout: &str) -> Self {
+            Self {
+                success: true,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            }
+        }
+
+        pub fn fail(stderr: &str) -> Self {
+            Self {
+                success: false,
+                stdout: String::new(),
+                stderr: stderr.to_string(),
+            }
+        }
+    }
+
+    /// A scripted git runner. Each rule matches commands by argument prefix and
+    /// replays its outputs in order, repeating the last one once exhausted.
+    /// Commands without a matching rule fail.
+    #[derive(Default)]
+    pub struct MockRunner {
+        rules: RefCell<Vec<(Vec<String>, VecDeque<GitOutput>)>>,
+        pub calls: RefCell<Vec<Vec<String>>>,
+        /// Environment variables passed with each recorded call, in the same order.
+        pub envs: RefCell<Vec<Vec<(String, String)>>>,
+    }
+
+    impl MockRunner {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn on(self, prefix: &[&str], output: GitOutput) -> Self {
+            let prefix: Vec<String> = prefix.iter().map(|part| (*part).to_string()).collect();
+            {
+                let mut rules = self.rules.borrow_mut();
+                match rules.iter_mut().find(|(existing, _)| *existing == prefix) {
+                    Some((_, outputs)) => outputs.push_back(output),
+                    None => rules.push((prefix, VecDeque::from([output]))),
+                }
+            }
+            self
+        }
+
+        /// Number of recorded calls whose arguments start with `prefix`.
+        pub fn count(&self, prefix: &[&str]) -> usize {
+            self.calls
+                .borrow()
+                .iter()
+                .filter(|call| {
+                    call.iter()
+                        .map(String::as_str)
+                        .take(prefix.len())
+                        .eq(prefix.iter().copied())
+                })
+                .count()
+        }
+    }
+
+    impl GitRunner for MockRunner {
+        fn git_with_env(&self, args: &[String], env: GitEnv) -> Result<GitOutput, Box<dyn Error>> {
+            self.calls.borrow_mut().push(args.to_vec());
+            self.envs.borrow_mut().push(
+                env.iter()
+                    .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                    .collect(),
+            );
+
+            let mut rules = self.rules.borrow_mut();
+            let rule = rules
+                .iter_mut()
+                .find(|(prefix, _)| args.starts_with(prefix));
+
+            Ok(match rule {
+                Some((_, outputs)) if outputs.len() > 1 => outputs.pop_front().unwrap_or_default(),
+                Some((_, outputs)) => outputs.front().cloned().unwrap_or_default(),
+                None => GitOutput::fail("unmocked git command"),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockRunner;
+    use super::*;
+
+    const LOCK_STDERR: &str = "fatal: Unable to create '/repo/.git/index.lock': File exists.";
+
+    #[test]
+    fn test_is_lock_error() {
+        assert!(is_lock_error(LOCK_STDERR));
+        assert!(!is_lock_error("error: pathspec 'nope' did not match"));
+    }
+
+    #[test]
+    fn test_format_timing() {
+        assert_eq!(
+            format_timing("rev-list", Duration::from_millis(1820)),
+            "rev-list: 1.82s"
+        );
+        assert_eq!(format_timing("checkout", Duration::ZERO), "checkout: 0.00s");
+    }
+
+    #[test]
+    fn test_subcommand_skips_global_options() {
+        assert_eq!(subcommand(&to_args(&["rev-parse", "HEAD"])), "rev-parse");
+        assert_eq!(
+            subcommand(&to_args(&["-C", "/tmp/x", "checkout", "abc"])),
+            "checkout"
+        );
+        assert_eq!(subcommand(&[]), "git");
+    }
+
+    #[test]
+    fn test_streamed_lines_match_buffered() {
+        let sample = "aaa 2024-01-01 first\nbbb 2024-01-02 second\n\nccc 2024-01-03 third";
+        let runner = MockRunner::new().on(&["log"], GitOutput::ok(sample));
+
+        let mut streamed = Vec::new();
+        let output = runner
+            .git_lines(&to_args(&["log"]), &mut |line| {
+                streamed.push(line.to_string());
+                Ok(())
+            })
+            .unwrap();
+
+        let buffered: Vec<String> = runner
+            .git(&to_args(&["log"]))
+            .unwrap()
+            .stdout
+            .lines()
+            .map(str::to_string)
+            .collect();
+
+        assert!(output.success);
+        assert!(output.stdout.is_empty());
+        assert_eq!(streamed, buffered);
+    }
+
+    #[test]
+    fn test_for_each_line_stops_on_error() {
+        let mut seen = 0;
+        let result = for_each_line("one\ntwo\nthree\n".as_bytes(), &mut |line| {
+            seen += 1;
+            if line == "two" {
+                Err("stop".into())
+            } else {
+                Ok(())
+            }
+        });
+
+        assert!(result.is_err());
+        assert_eq!(seen, 2);
+    }
+
+    #[test]
+    fn test_for_each_line_stop_reading_is_not_an_error() {
+        let mut seen = Vec::new();
+        let result = for_each_line("one\ntwo\nthree\n".as_bytes(), &mut |line| {
+            seen.push(line.to_string());
+            if line == "two" {
+                Err(StopReading.into())
+            } else {
+                Ok(())
+            }
+        });
+
+        result.unwrap();
+        assert_eq!(seen, ["one", "two"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_with_timeout_kills_long_running_command() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let start = Instant::now();
+
+        let err = wait_with_timeout(
+            &mut child,
+            &to_args(&["rev-list"]),
+            start,
+            Some(Duration::from_millis(100)),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), messages::git_timed_out("rev-list", 0.1));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        // Already reaped: the child was killed, not left running.
+        let status = child.try_wait().unwrap().unwrap();
+        assert!(!status.success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_with_timeout_returns_status_in_time() {
+        let mut child = Command::new("true").spawn().unwrap();
+
+        let status = wait_with_timeout(
+            &mut child,
+            &[],
+            Instant::now(),
+            Some(Duration::from_secs(30)),
+        )
+        .unwrap();
+
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_in_dir_prefixes_commands() {
+        let inner = MockRunner::new().on(&["-C", "/work/repo", "status"], GitOutput::ok("clean"));
+        let runner = InDir::new(&inner, Path::new("/work/repo"));
+
+        assert_eq!(runner.git(&to_args(&["status"])).unwrap().stdout, "clean");
+    }
+
+    #[test]
+    fn test_timed_runner_passes_through() {
+        let inner = MockRunner::new().on(&["rev-parse"], GitOutput::ok("abc\n"));
+        let timed = TimedRunner::new(&inner);
+
+        let output = timed.git(&to_args(&["rev-parse", "HEAD"])).unwrap();
+        assert_eq!(output.stdout, "abc\n");
+        assert_eq!(inner.count(&["rev-parse"]), 1);
+    }
+
+    #[test]
+    fn test_lock_failure_then_success_is_retried() {
+        let runner = MockRunner::new()
+            .on(&["checkout"], GitOutput::fail(LOCK_STDERR))
+            .on(&["checkout"], GitOutput::ok(""));
+
+        let output = run_with_lock_retries(
+            &runner,
+            &to_args(&["checkout", "abc"]),
+            &[],
+            3,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(output.success);
+        assert_eq!(runner.count(&["checkout"]), 2);
+    }
+
+    #[test]
+    fn test_lock_retries_are_bounded() {
+        let runner = MockRunner::new().on(&["checkout"], GitOutput::fail(LOCK_STDERR));
+
+        let output = run_with_lock_retries(
+            &runner,
+            &to_args(&["checkout", "abc"]),
+            &[],
+            2,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(!output.success);
+        assert_eq!(runner.count(&["checkout"]), 3);
+    }
+
+    #[test]
+    fn test_other_failures_are_not_retried() {
+        let runner = MockRunner::new().on(
+            &["checkout"],
+            GitOutput::fail("error: Your local changes would be overwritten"),
+        );
+
+        let output = run_with_lock_retries(
+            &runner,
+            &to_args(&["checkout", "abc"]),
+            &[],
+            3,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        assert!(!output.success);
+        assert_eq!(runner.count(&["checkout"]), 1);
+    }
+}
@@ -0,0 +1,20 @@
+# checkout-ago shell integration for bash.
+# Usage: eval "$(git-checkout-ago shell-init bash)"
+#
+#   ago 2d     jump back, remembering where you were
+#   ago back   return to the remembered branch or commit
+
+ago() {
+    if [ "$1" = "back" ]; then
+        if [ -z "$CHECKOUT_AGO_RETURN" ]; then
+            echo "ago: no return point recorded" >&2
+            return 1
+        fi
+        git checkout "$CHECKOUT_AGO_RETURN" && unset CHECKOUT_AGO_RETURN
+        return
+    fi
+
+    local here
+    here="$(git symbolic-ref --quiet --short HEAD || git rev-parse HEAD)" || return
+    git-checkout-ago "$@" && CHECKOUT_AGO_RETURN="${CHECKOUT_AGO_RETURN:-$here}"
+}
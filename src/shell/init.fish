@@ -0,0 +1,20 @@
+# checkout-ago shell integration for fish.
+# Usage: git-checkout-ago shell-init fish | source
+#
+#   ago 2d     jump back, remembering where you were
+#   ago back   return to the remembered branch or commit
+
+function ago
+    if test "$argv[1]" = back
+        if not set -q CHECKOUT_AGO_RETURN
+            echo "ago: no return point recorded" >&2
+            return 1
+        end
+        git checkout $CHECKOUT_AGO_RETURN; and set -e CHECKOUT_AGO_RETURN
+        return
+    end
+
+    set -l here (git symbolic-ref --quiet --short HEAD; or git rev-parse HEAD); or return
+    git-checkout-ago $argv; or return
+    set -q CHECKOUT_AGO_RETURN; or set -g CHECKOUT_AGO_RETURN $here
+end
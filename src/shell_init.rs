@@ -0,0 +1,34 @@
+use clap::ValueEnum;
+
+/// Shells `shell-init` can emit a wrapper function for.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Shell code defining an `ago` function that wraps the binary and remembers
+/// where to return to.
+pub fn snippet(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => include_str!("shell/init.bash"),
+        Shell::Zsh => include_str!("shell/init.zsh"),
+        Shell::Fish => include_str!("shell/init.fish"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets_reference_binary() {
+        for shell in Shell::value_variants() {
+            let snippet = snippet(*shell);
+
+            assert!(!snippet.trim().is_empty());
+            assert!(snippet.contains(env!("CARGO_BIN_NAME")));
+        }
+    }
+}
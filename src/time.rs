@@ -0,0 +1,1080 @@
+use crate::error::AgoError;
+use crate::messages;
+use clap::ValueEnum;
+use std::collections::BTreeSet;
+use std::error::Error;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Which day a week is considered to start on.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+/// Everything needed to turn a user-supplied time expression into a cutoff.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeContext<'a> {
+    /// Current time, in seconds since the Unix epoch.
+    pub now: i64,
+    pub week_start: WeekStart,
+    /// Seconds the local timezone is ahead of UTC, for calendar boundaries.
+    pub utc_offset: i64,
+    /// Reject shorthand whose meaning is easy to mistake (see [`ambiguous_unit`]).
+    pub strict: bool,
+    /// Days (since the Unix epoch) skipped, like weekends, when counting business days.
+    pub holidays: &'a [i64],
+}
+
+/// The point in time passed to git as `--before`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cutoff {
+    /// A relative expression git understands, e.g. `2 days` (git is given `2 days ago`).
+    Ago(String),
+    /// An expression passed to git verbatim, e.g. `2024-01-31`.
+    Exact(String),
+    /// An absolute Unix timestamp.
+    At(i64),
+}
+
+/// A calendar boundary like "start of this week".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Boundary {
+    Day,
+    Week,
+    Month,
+}
+
+impl Boundary {
+    fn parse(input: &str) -> Option<Self> {
+        match input {
+            "start-of-day" => Some(Self::Day),
+            "start-of-week" => Some(Self::Week),
+            "start-of-month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    /// Timestamp of the most recent boundary at or before `now`, at local
+    /// midnight in a timezone `utc_offset` seconds ahead of UTC.
+    pub fn timestamp(self, now: i64, week_start: WeekStart, utc_offset: i64) -> i64 {
+        let today = (now + utc_offset).div_euclid(SECONDS_PER_DAY);
+
+        let day = match self {
+            Self::Day => today,
+            Self::Week => {
+                // 1970-01-01 was a Thursday.
+                let offset = match week_start {
+                    WeekStart::Monday => 3,
+                    WeekStart::Sunday => 4,
+                };
+                today - (today + offset).rem_euclid(7)
+            }
+            Self::Month => {
+                let (year, month, _) = civil_from_days(today);
+                days_from_civil(year, month, 1)
+            }
+        };
+
+        day * SECONDS_PER_DAY - utc_offset
+    }
+}
+
+/// Parse the trailing `+HHMM`/`-HHMM` offset of a git ident line such as
+/// `Name <email> 1700000000 +0100` into seconds ahead of UTC.
+pub fn parse_utc_offset(ident: &str) -> Option<i64> {
+    let zone = ident.trim().rsplit(' ').next()?;
+    let (sign, digits) = match zone.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Days since the Unix epoch of the Monday starting ISO week 1 of `year`,
+/// which is the week containing January 4th.
+fn iso_week_one(year: i64) -> i64 {
+    let jan4 = days_from_civil(year, 1, 4);
+
+    // 1970-01-01 was a Thursday.
+    jan4 - (jan4 + 3).rem_euclid(7)
+}
+
+/// Parse an ISO week such as `2024-W07` into `(year, week)`. Weeks the year
+/// doesn't have (like `2023-W53`) are rejected.
+pub fn parse_iso_week(input: &str) -> Option<(i64, u32)> {
+    let (year, week) = input.trim().split_once("-W")?;
+
+    if year.len() != 4
+        || week.len() != 2
+        || !format!("{year}{week}").bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let year: i64 = year.parse().ok()?;
+    let week: u32 = week.parse().ok()?;
+    let weeks_in_year = (iso_week_one(year + 1) - iso_week_one(year)) / 7;
+
+    (week >= 1 && i64::from(week) <= weeks_in_year).then_some((year, week))
+}
+
+/// Unix timestamp of the last second of an ISO week, in UTC.
+pub fn iso_week_end(year: i64, week: u32) -> i64 {
+    (iso_week_one(year) + 7 * i64::from(week)) * SECONDS_PER_DAY - 1
+}
+
+/// Parse a `YYYY-MM-DD` date into days since the Unix epoch, rejecting
+/// dates that don't exist such as `2023-02-29`.
+pub fn parse_date(input: &str) -> Option<i64> {
+    let mut parts = input.trim().splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+
+    if year.len() != 4
+        || month.len() != 2
+        || day.len() != 2
+        || !format!("{year}{month}{day}")
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let (year, month, day) = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    let days = days_from_civil(year, month, day);
+
+    (civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// Parse a holidays file: one `YYYY-MM-DD` per line, with blank lines and
+/// `#` comments ignored.
+pub fn parse_holidays(text: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+    let mut holidays = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let date = line.split('#').next().unwrap_or_default().trim();
+
+        if date.is_empty() {
+            continue;
+        }
+
+        let day = parse_date(date)
+            .ok_or_else(|| AgoError::InvalidTime(messages::invalid_holiday(index + 1, line)))?;
+        holidays.push(day);
+    }
+
+    Ok(holidays)
+}
+
+/// The number of days in business-day shorthand such as `3bd` or
+/// `3 business days`.
+fn parse_business_days(input: &str) -> Option<u32> {
+    let input = input.trim();
+
+    if let Some(number) = input.strip_suffix("bd") {
+        return number.parse().ok();
+    }
+
+    let mut words = input.split_whitespace();
+    let (number, business, unit) = (words.next()?, words.next()?, words.next()?);
+
+    (words.next().is_none() && business == "business" && matches!(unit, "day" | "days"))
+        .then(|| number.parse().ok())
+        .flatten()
+}
+
+fn is_weekend(day: i64) -> bool {
+    // 1970-01-01 was a Thursday, so this counts from Monday = 0.
+    (day + 3).rem_euclid(7) >= 5
+}
+
+/// The first weekday one or more days from `day` in the direction of `step`.
+fn next_weekday(day: i64, step: i64) -> i64 {
+    let mut day = day + step;
+    while is_weekend(day) {
+        day += step;
+    }
+    day
+}
+
+/// The day `count` weekdays from `day` in the direction of `step`. Once on a
+/// weekday every whole week is exactly five of them, so only the remainder is
+/// walked.
+fn shift_weekdays(day: i64, count: u64, step: i64) -> i64 {
+    let day = next_weekday(day, step);
+    let remaining = count - 1;
+    let weeks = i64::try_from(remaining / 5).unwrap_or(i64::MAX);
+    let mut day = day.saturating_add(step.saturating_mul(weeks).saturating_mul(7));
+
+    for _ in 0..remaining % 5 {
+        day = next_weekday(day, step);
+    }
+
+    day
+}
+
+/// The day `count` business days from `today` in the direction of `step`,
+/// skipping weekends and `holidays` (all in days since the Unix epoch).
+fn shift_business_days(today: i64, count: u32, step: i64, holidays: &[i64]) -> i64 {
+    let holidays: BTreeSet<i64> = holidays
+        .iter()
+        .copied()
+        .filter(|&day| !is_weekend(day))
+        .collect();
+    let mut day = today;
+    let mut remaining = u64::from(count);
+
+    // Every holiday passed costs one more weekday, which may pass more of them.
+    while remaining > 0 {
+        let next = shift_weekdays(day, remaining, step);
+        let passed = if step < 0 {
+            holidays.range(next..day)
+        } else {
+            holidays.range(day + 1..=next)
+        };
+        remaining = passed.count() as u64;
+        day = next;
+    }
+
+    day
+}
+
+/// The day `count` business days before `today`, skipping weekends and
+/// `holidays` (all in days since the Unix epoch).
+pub fn business_days_before(today: i64, count: u32, holidays: &[i64]) -> i64 {
+    shift_business_days(today, count, -1, holidays)
+}
+
+/// The day `count` business days after `today`, skipping weekends and
+/// `holidays` (all in days since the Unix epoch).
+pub fn business_days_after(today: i64, count: u32, holidays: &[i64]) -> i64 {
+    shift_business_days(today, count, 1, holidays)
+}
+
+/// Days since the Unix epoch for a proleptic Gregorian date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian `(year, month, day)` for a count of days since the Unix epoch.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    // Both values are bounded by the calendar arithmetic above.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    (year, month as u32, day as u32)
+}
+
+/// A unit of time git understands.
+pub struct Unit {
+    /// Shorthand accepted after a number, e.g. `d` in `2d`.
+    pub short: Option<&'static str>,
+    /// Longer alternatives to `short`, e.g. `min` for minutes. Units that
+    /// only have these are approximations and are never produced from seconds.
+    pub other_shorts: &'static [&'static str],
+    /// Plural name git understands, e.g. `days`.
+    pub name: &'static str,
+    /// Length in seconds. Months and years are approximated as 30 and 365 days.
+    pub seconds: i64,
+}
+
+impl Unit {
+    /// Every shorthand accepted for this unit, `short` first.
+    pub fn shorthands(&self) -> Vec<&'static str> {
+        self.short
+            .iter()
+            .chain(self.other_shorts)
+            .copied()
+            .collect()
+    }
+}
+
+pub const UNITS: &[Unit] = &[
+    Unit {
+        short: Some("s"),
+        other_shorts: &[],
+        name: "seconds",
+        seconds: 1,
+    },
+    Unit {
+        short: Some("m"),
+        other_shorts: &["min"],
+        name: "minutes",
+        seconds: 60,
+    },
+    Unit {
+        short: Some("h"),
+        other_shorts: &[],
+        name: "hours",
+        seconds: 3600,
+    },
+    Unit {
+        short: Some("d"),
+        other_shorts: &[],
+        name: "days",
+        seconds: SECONDS_PER_DAY,
+    },
+    Unit {
+        short: Some("w"),
+        other_shorts: &[],
+        name: "weeks",
+        seconds: 7 * SECONDS_PER_DAY,
+    },
+    Unit {
+        short: None,
+        other_shorts: &["mo"],
+        name: "months",
+        seconds: 30 * SECONDS_PER_DAY,
+    },
+    Unit {
+        short: None,
+        other_shorts: &[],
+        name: "years",
+        seconds: 365 * SECONDS_PER_DAY,
+    },
+];
+
+/// A unit TIME can be counted in that isn't a length of time, so is resolved
+/// here instead of by git.
+pub struct Count {
+    /// Shorthands accepted after a number, e.g. `bd` in `3bd`.
+    pub shorthands: &'static [&'static str],
+    pub name: &'static str,
+}
+
+/// Units listed by `units` alongside [`UNITS`].
+pub const COUNTS: &[Count] = &[
+    Count {
+        shorthands: &["bd"],
+        name: "business days",
+    },
+    Count {
+        shorthands: &["t"],
+        name: "tags",
+    },
+];
+
+/// Convert shorthand like `2d`, `3h`, `1w` into git-compatible strings.
+/// Fractional shorthand such as `1.5d` is converted to whole units (`36 hours`),
+/// rounding to the nearest second.
+/// If the input doesn't match shorthand, return it unchanged.
+pub fn normalize_ago(input: &str) -> String {
+    let input = input.trim();
+
+    if input.is_empty() {
+        return input.to_string();
+    }
+
+    let (number, unit) = input.split_at(
+        input
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(input.len()),
+    );
+
+    if number.is_empty() || unit.is_empty() {
+        return input.to_string();
+    }
+
+    let Some(expanded) = UNITS
+        .iter()
+        .find(|candidate| candidate.shorthands().contains(&unit))
+    else {
+        return input.to_string();
+    };
+
+    if !number.contains('.') {
+        return format!("{number} {}", expanded.name);
+    }
+
+    match fractional_seconds(number, expanded.seconds) {
+        Some(seconds) => whole_unit_expression(seconds),
+        None => input.to_string(),
+    }
+}
+
+/// Seconds in `number` (a decimal like `1.5`) of a unit `unit_seconds` long,
+/// rounded half-up to the nearest whole second.
+fn fractional_seconds(number: &str, unit_seconds: i64) -> Option<i64> {
+    let (whole, fraction) = number.split_once('.')?;
+
+    if whole.is_empty() || fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let scale = 10i64.checked_pow(u32::try_from(fraction.len()).ok()?)?;
+    let scaled: i64 = format!("{whole}{fraction}").parse().ok()?;
+    let total = scaled.checked_mul(unit_seconds)?;
+
+    Some((total + scale / 2) / scale)
+}
+
+/// Express `seconds` in the largest unit that divides it exactly, e.g.
+/// 129600 seconds as `36 hours`. Months and years are never used since
+/// they are approximations.
+fn whole_unit_expression(seconds: i64) -> String {
+    let unit = UNITS
+        .iter()
+        .filter(|unit| unit.short.is_some() && seconds != 0 && seconds % unit.seconds == 0)
+        .max_by_key(|unit| unit.seconds)
+        .unwrap_or(&UNITS[0]);
+
+    format!("{} {}", seconds / unit.seconds, unit.name)
+}
+
+/// Compact rendering of a length of time using its two largest shorthand
+/// units, e.g. `1d 2h` or `45s`.
+pub fn format_delta(seconds: i64) -> String {
+    let mut remaining = seconds.abs();
+    let mut parts = Vec::new();
+
+    for unit in UNITS.iter().rev().filter(|unit| unit.short.is_some()) {
+        if remaining >= unit.seconds && parts.len() < 2 {
+            parts.push(format!(
+                "{}{}",
+                remaining / unit.seconds,
+                unit.short.unwrap_or_default()
+            ));
+            remaining %= unit.seconds;
+        } else if !parts.is_empty() {
+            // Only adjacent units, so `1w 3s` never appears.
+            break;
+        }
+    }
+
+    if parts.is_empty() {
+        return "0s".to_string();
+    }
+
+    parts.join(" ")
+}
+
+/// How long before `now` the Unix `timestamp` was, e.g. `2d 3h ago`; a
+/// timestamp after `now` reads `in 2d 3h`.
+pub fn humanize_since(timestamp: i64, now: i64) -> String {
+    let delta = format_delta(now - timestamp);
+
+    if timestamp > now {
+        format!("in {delta}")
+    } else {
+        format!("{delta} ago")
+    }
+}
+
+/// Length in seconds of a relative expression such as `2 days`, `3h` or
+/// `1 week`. Returns `None` for anything more elaborate than `<number> <unit>`.
+pub fn parse_duration(input: &str) -> Option<i64> {
+    let normalized = normalize_ago(input);
+    let mut words = normalized.split_whitespace();
+    let (number, unit) = (words.next()?, words.next()?);
+
+    if words.next().is_some() {
+        return None;
+    }
+
+    let number: i64 = number.parse().ok()?;
+    let unit = UNITS.iter().find(|candidate| {
+        candidate.name == unit || candidate.name.strip_suffix('s') == Some(unit)
+    })?;
+
+    number.checked_mul(unit.seconds)
+}
+
+/// The shorthand unit in `input` if it is one people often misread, such as
+/// `m` in `3m`: git and this tool read it as minutes, but it's easily meant as months.
+pub fn ambiguous_unit(input: &str) -> Option<&str> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = input.split_at(split);
+
+    (!number.is_empty() && unit == "m").then_some(unit)
+}
+
+/// Whether the input already names a point in time (e.g. `2024-01-31`),
+/// in which case appending " ago" would make no sense.
+pub fn looks_absolute(input: &str) -> bool {
+    let bytes = input.trim().as_bytes();
+
+    bytes.len() >= 5 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-'
+}
+
+/// Parse git's `@<seconds>` epoch syntax. Returns `None` if the input doesn't
+/// start with `@`, and an error if what follows isn't a whole number of seconds.
+fn parse_epoch(input: &str) -> Option<Result<i64, Box<dyn Error>>> {
+    let digits = input.strip_prefix('@')?;
+
+    Some(
+        digits
+            .parse::<i64>()
+            .map_err(|_| AgoError::InvalidTime(messages::invalid_epoch(input)).into()),
+    )
+}
+
+/// Turn the user's time expression into a typed cutoff.
+/// In `raw` mode the string is passed through without shorthand expansion.
+pub fn parse_cutoff(input: &str, raw: bool, ctx: &TimeContext) -> Result<Cutoff, Box<dyn Error>> {
+    if let Some(epoch) = parse_epoch(input.trim()) {
+        return Ok(Cutoff::At(epoch?));
+    }
+
+    if raw {
+        return Ok(if looks_absolute(input) {
+            Cutoff::Exact(input.to_string())
+        } else {
+            Cutoff::Ago(input.to_string())
+        });
+    }
+
+    if let Some(boundary) = Boundary::parse(input.trim()) {
+        return Ok(Cutoff::At(boundary.timestamp(
+            ctx.now,
+            ctx.week_start,
+            ctx.utc_offset,
+        )));
+    }
+
+    // Business days keep the time of day, so `1bd` on a Monday afternoon is
+    // Friday afternoon.
+    if let Some(count) = parse_business_days(input) {
+        let day = business_days_before(ctx.now.div_euclid(SECONDS_PER_DAY), count, ctx.holidays);
+        return Ok(Cutoff::At(
+            day * SECONDS_PER_DAY + ctx.now.rem_euclid(SECONDS_PER_DAY),
+        ));
+    }
+
+    if ctx.strict
+        && let Some(unit) = ambiguous_unit(input)
+    {
+        return Err(AgoError::InvalidTime(messages::ambiguous_unit(unit)).into());
+    }
+
+    Ok(Cutoff::Ago(normalize_ago(input)))
+}
+
+/// The Unix timestamp `input` after `ctx.now`, parsed like [`parse_cutoff`] but
+/// counting forward. `None` when `input` is not an offset, like a date or a
+/// calendar boundary.
+pub fn cutoff_after(
+    input: &str,
+    raw: bool,
+    ctx: &TimeContext,
+) -> Result<Option<i64>, Box<dyn Error>> {
+    if !raw && let Some(count) = parse_business_days(input) {
+        let day = business_days_after(ctx.now.div_euclid(SECONDS_PER_DAY), count, ctx.holidays);
+        return Ok(Some(
+            day * SECONDS_PER_DAY + ctx.now.rem_euclid(SECONDS_PER_DAY),
+        ));
+    }
+
+    Ok(parse_cutoff(input, raw, ctx)?
+        .duration()
+        .map(|seconds| ctx.now.saturating_add(seconds)))
+}
+
+impl Cutoff {
+    /// The cutoff as a Unix timestamp, when it can be computed without git.
+    pub fn timestamp(&self, now: i64) -> Option<i64> {
+        match self {
+            Self::Ago(ago) => parse_duration(ago).map(|seconds| now - seconds),
+            Self::Exact(_) => None,
+            Self::At(timestamp) => Some(*timestamp),
+        }
+    }
+
+    /// How far before now a relative cutoff lies, in seconds, when that can be
+    /// computed without git.
+    pub fn duration(&self) -> Option<i64> {
+        match self {
+            Self::Ago(ago) => parse_duration(ago),
+            Self::Exact(_) | Self::At(_) => None,
+        }
+    }
+
+    /// The value git's `--before=` option should receive.
+    pub fn git_before(&self) -> String {
+        match self {
+            Self::Ago(ago) => format!("{ago} ago"),
+            Self::Exact(exact) => exact.clone(),
+            Self::At(timestamp) => format!("@{timestamp}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wednesday 2024-02-14 15:30:00 UTC.
+    const NOW: i64 = 1_707_924_600;
+
+    fn ctx(week_start: WeekStart) -> TimeContext<'static> {
+        TimeContext {
+            now: NOW,
+            week_start,
+            utc_offset: 0,
+            strict: false,
+            holidays: &[],
+        }
+    }
+
+    #[test]
+    fn test_normalize_shorthand_days() {
+        assert_eq!(normalize_ago("2d"), "2 days");
+    }
+
+    #[test]
+    fn test_normalize_shorthand_hours() {
+        assert_eq!(normalize_ago("3h"), "3 hours");
+    }
+
+    #[test]
+    fn test_normalize_shorthand_weeks() {
+        assert_eq!(normalize_ago("1w"), "1 weeks");
+    }
+
+    #[test]
+    fn test_normalize_shorthand_minutes() {
+        assert_eq!(normalize_ago("15m"), "15 minutes");
+    }
+
+    #[test]
+    fn test_normalize_unambiguous_minutes_and_months() {
+        assert_eq!(normalize_ago("5min"), "5 minutes");
+        assert_eq!(normalize_ago("3mo"), "3 months");
+        assert_eq!(normalize_ago("1.5mo"), "45 days");
+    }
+
+    #[test]
+    fn test_strict_rejects_ambiguous_m() {
+        let strict = TimeContext {
+            strict: true,
+            ..ctx(WeekStart::Monday)
+        };
+
+        let err = parse_cutoff("3m", false, &strict).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ambiguous unit 'm'; use 'min' for minutes or 'mo' for months"
+        );
+        assert_eq!(
+            parse_cutoff("3min", false, &strict).unwrap(),
+            Cutoff::Ago("3 minutes".into())
+        );
+        assert_eq!(
+            parse_cutoff("3mo", false, &strict).unwrap(),
+            Cutoff::Ago("3 months".into())
+        );
+    }
+
+    #[test]
+    fn test_non_strict_keeps_m_as_minutes() {
+        assert_eq!(
+            parse_cutoff("3m", false, &ctx(WeekStart::Monday)).unwrap(),
+            Cutoff::Ago("3 minutes".into())
+        );
+        assert_eq!(ambiguous_unit("3 minutes"), None);
+        assert_eq!(ambiguous_unit("m"), None);
+    }
+
+    #[test]
+    fn test_normalize_shorthand_seconds() {
+        assert_eq!(normalize_ago("30s"), "30 seconds");
+    }
+
+    #[test]
+    fn test_normalize_passthrough() {
+        assert_eq!(normalize_ago("2 days"), "2 days");
+        assert_eq!(normalize_ago("1 week"), "1 week");
+    }
+
+    #[test]
+    fn test_normalize_invalid_unit() {
+        assert_eq!(normalize_ago("10x"), "10x");
+    }
+
+    #[test]
+    fn test_normalize_fractional_days() {
+        assert_eq!(normalize_ago("1.5d"), "36 hours");
+    }
+
+    #[test]
+    fn test_normalize_fractional_hours() {
+        assert_eq!(normalize_ago("0.5h"), "30 minutes");
+    }
+
+    #[test]
+    fn test_normalize_fractional_weeks() {
+        assert_eq!(normalize_ago("2.25w"), "378 hours");
+        assert_eq!(normalize_ago("0.5w"), "84 hours");
+        assert_eq!(normalize_ago("2.0w"), "2 weeks");
+    }
+
+    #[test]
+    fn test_normalize_fractional_rounds_to_seconds() {
+        assert_eq!(normalize_ago("0.5s"), "1 seconds");
+        assert_eq!(normalize_ago("0.0001m"), "0 seconds");
+    }
+
+    #[test]
+    fn test_normalize_malformed_fraction_passthrough() {
+        assert_eq!(normalize_ago("1.d"), "1.d");
+        assert_eq!(normalize_ago(".5d"), ".5d");
+        assert_eq!(normalize_ago("1.2.3d"), "1.2.3d");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2d"), Some(2 * SECONDS_PER_DAY));
+        assert_eq!(parse_duration("1.5d"), Some(36 * 3600));
+        assert_eq!(parse_duration("3 hours"), Some(3 * 3600));
+        assert_eq!(parse_duration("1 week"), Some(7 * SECONDS_PER_DAY));
+        assert_eq!(parse_duration("1 month 2 days"), None);
+        assert_eq!(parse_duration("yesterday"), None);
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(0), "0s");
+        assert_eq!(format_delta(45), "45s");
+        assert_eq!(format_delta(93_600), "1d 2h");
+        assert_eq!(format_delta(-7_200), "2h");
+        assert_eq!(format_delta(7 * 86_400 + 3), "1w");
+    }
+
+    #[test]
+    fn test_unit_shorthands() {
+        let minutes = UNITS.iter().find(|unit| unit.name == "minutes").unwrap();
+        let years = UNITS.iter().find(|unit| unit.name == "years").unwrap();
+
+        assert_eq!(minutes.shorthands(), ["m", "min"]);
+        assert!(years.shorthands().is_empty());
+    }
+
+    #[test]
+    fn test_humanize_since() {
+        assert_eq!(humanize_since(NOW - 183_600, NOW), "2d 3h ago");
+        assert_eq!(humanize_since(NOW, NOW), "0s ago");
+        assert_eq!(humanize_since(NOW + 90, NOW), "in 1m 30s");
+    }
+
+    #[test]
+    fn test_cutoff_timestamp() {
+        assert_eq!(
+            Cutoff::Ago("1 hours".into()).timestamp(NOW),
+            Some(NOW - 3600)
+        );
+        assert_eq!(Cutoff::At(42).timestamp(NOW), Some(42));
+        assert_eq!(Cutoff::Exact("2024-01-31".into()).timestamp(NOW), None);
+    }
+
+    #[test]
+    fn test_business_days_skip_the_weekend() {
+        let ctx = ctx(WeekStart::Monday);
+        let at = |day: i64| day * SECONDS_PER_DAY + 15 * 3600 + 30 * 60;
+
+        // Wednesday back three weekdays is the previous Friday.
+        assert_eq!(
+            parse_cutoff("3bd", false, &ctx).unwrap(),
+            Cutoff::At(at(days_from_civil(2024, 2, 9)))
+        );
+        assert_eq!(
+            parse_cutoff("5 business days", false, &ctx).unwrap(),
+            Cutoff::At(at(days_from_civil(2024, 2, 7)))
+        );
+        assert_eq!(
+            parse_cutoff("1 business day", false, &ctx).unwrap(),
+            Cutoff::At(at(days_from_civil(2024, 2, 13)))
+        );
+        assert_eq!(parse_cutoff("0bd", false, &ctx).unwrap(), Cutoff::At(NOW));
+        assert_eq!(parse_business_days("3 business weeks"), None);
+        assert_eq!(parse_business_days("xbd"), None);
+    }
+
+    #[test]
+    fn test_business_days_skip_holidays() {
+        let monday = days_from_civil(2024, 2, 12);
+        let holidays = [monday];
+        let ctx = TimeContext {
+            holidays: &holidays,
+            ..ctx(WeekStart::Monday)
+        };
+
+        assert_eq!(
+            business_days_before(NOW / SECONDS_PER_DAY, 2, &holidays),
+            days_from_civil(2024, 2, 9)
+        );
+        assert_eq!(
+            parse_cutoff("3bd", false, &ctx).unwrap().timestamp(NOW),
+            Some(days_from_civil(2024, 2, 8) * SECONDS_PER_DAY + 15 * 3600 + 30 * 60)
+        );
+    }
+
+    #[test]
+    fn test_cutoff_after() {
+        let ctx = ctx(WeekStart::Monday);
+        let friday = days_from_civil(2024, 2, 16);
+
+        assert_eq!(
+            cutoff_after("2d", false, &ctx).unwrap(),
+            Some(NOW + 2 * SECONDS_PER_DAY)
+        );
+        assert_eq!(
+            cutoff_after("4bd", false, &ctx).unwrap(),
+            Some(days_from_civil(2024, 2, 20) * SECONDS_PER_DAY + NOW.rem_euclid(SECONDS_PER_DAY))
+        );
+        assert_eq!(business_days_after(friday, 1, &[]), friday + 3);
+        assert_eq!(cutoff_after("start-of-week", false, &ctx).unwrap(), None);
+        assert_eq!(cutoff_after("2024-01-31", false, &ctx).unwrap(), None);
+        assert_eq!(
+            cutoff_after(
+                "2m",
+                false,
+                &TimeContext {
+                    strict: true,
+                    ..ctx
+                }
+            )
+            .unwrap_err()
+            .to_string(),
+            messages::ambiguous_unit("m")
+        );
+        assert_eq!(
+            cutoff_after(
+                "1s",
+                false,
+                &TimeContext {
+                    now: i64::MAX,
+                    ..ctx
+                }
+            )
+            .unwrap(),
+            Some(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_business_days_match_walking_day_by_day() {
+        // The slow but obviously right count the arithmetic must agree with.
+        fn walk(today: i64, count: u32, step: i64, holidays: &[i64]) -> i64 {
+            let (mut day, mut remaining) = (today, count);
+            while remaining > 0 {
+                day += step;
+                if !is_weekend(day) && !holidays.contains(&day) {
+                    remaining -= 1;
+                }
+            }
+            day
+        }
+
+        let monday = days_from_civil(2024, 2, 12);
+        let holidays = [monday + 1, monday + 1, monday + 5, monday + 8, monday - 3];
+
+        for today in monday - 7..monday + 7 {
+            for count in 0..30 {
+                assert_eq!(
+                    business_days_before(today, count, &holidays),
+                    walk(today, count, -1, &holidays)
+                );
+                assert_eq!(
+                    business_days_after(today, count, &holidays),
+                    walk(today, count, 1, &holidays)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_business_days_huge_count_is_fast() {
+        let monday = days_from_civil(2024, 2, 12);
+
+        assert_eq!(
+            business_days_before(monday, u32::MAX, &[]),
+            monday - i64::from(u32::MAX) / 5 * 7
+        );
+        assert!(parse_cutoff("4000000000bd", false, &ctx(WeekStart::Monday)).is_ok());
+    }
+
+    #[test]
+    fn test_parse_holidays() {
+        let text = "# company holidays\n2024-12-25\n\n2024-12-26  # boxing day\n";
+
+        assert_eq!(
+            parse_holidays(text).unwrap(),
+            [days_from_civil(2024, 12, 25), days_from_civil(2024, 12, 26)]
+        );
+        assert_eq!(
+            parse_holidays("2024-12-25\n2023-02-29\n")
+                .unwrap_err()
+                .to_string(),
+            messages::invalid_holiday(2, "2023-02-29")
+        );
+        assert_eq!(parse_date("2024-2-01"), None);
+    }
+
+    #[test]
+    fn test_civil_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_start_of_day() {
+        let cutoff = parse_cutoff("start-of-day", false, &ctx(WeekStart::Monday)).unwrap();
+        assert_eq!(cutoff, Cutoff::At(1_707_868_800));
+    }
+
+    #[test]
+    fn test_start_of_week_monday() {
+        let cutoff = parse_cutoff("start-of-week", false, &ctx(WeekStart::Monday)).unwrap();
+        assert_eq!(cutoff, Cutoff::At(1_707_696_000));
+    }
+
+    #[test]
+    fn test_start_of_week_sunday() {
+        let cutoff = parse_cutoff("start-of-week", false, &ctx(WeekStart::Sunday)).unwrap();
+        assert_eq!(cutoff, Cutoff::At(1_707_609_600));
+    }
+
+    #[test]
+    fn test_start_of_week_on_the_start_day() {
+        // Monday 2024-02-12 09:00 UTC is its own week start.
+        let monday = 1_707_696_000 + 9 * 3600;
+        assert_eq!(
+            Boundary::Week.timestamp(monday, WeekStart::Monday, 0),
+            1_707_696_000
+        );
+    }
+
+    #[test]
+    fn test_boundaries_use_the_local_offset() {
+        // NOW is 2024-02-14 15:30 UTC: still the 14th at UTC-5, already the 15th at UTC+10.
+        let behind = TimeContext {
+            utc_offset: -5 * 3600,
+            ..ctx(WeekStart::Monday)
+        };
+        let ahead = TimeContext {
+            utc_offset: 10 * 3600,
+            ..ctx(WeekStart::Monday)
+        };
+
+        assert_eq!(
+            parse_cutoff("start-of-day", false, &behind).unwrap(),
+            Cutoff::At(1_707_868_800 + 5 * 3600)
+        );
+        assert_eq!(
+            parse_cutoff("start-of-day", false, &ahead).unwrap(),
+            Cutoff::At(1_707_955_200 - 10 * 3600)
+        );
+        assert_eq!(
+            parse_cutoff("start-of-month", false, &ahead).unwrap(),
+            Cutoff::At(1_706_745_600 - 10 * 3600)
+        );
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("a <a@b> 1700000000 +0000"), Some(0));
+        assert_eq!(parse_utc_offset("a <a@b> 1700000000 +0530\n"), Some(19_800));
+        assert_eq!(parse_utc_offset("a <a@b> 1700000000 -0400"), Some(-14_400));
+        assert_eq!(parse_utc_offset("a <a@b> 1700000000"), None);
+        assert_eq!(parse_utc_offset("a <a@b> 1700000000 +04"), None);
+    }
+
+    #[test]
+    fn test_start_of_month() {
+        let cutoff = parse_cutoff("start-of-month", false, &ctx(WeekStart::Monday)).unwrap();
+        assert_eq!(cutoff, Cutoff::At(1_706_745_600));
+    }
+
+    #[test]
+    fn test_iso_week_end() {
+        // 2024-W07 runs Monday 2024-02-12 to Sunday 2024-02-18.
+        assert_eq!(
+            iso_week_end(2024, 7),
+            days_from_civil(2024, 2, 19) * 86_400 - 1
+        );
+        // 2021-W01 starts on 2021-01-04; 2020 had 53 weeks.
+        assert_eq!(
+            iso_week_end(2021, 1),
+            days_from_civil(2021, 1, 11) * 86_400 - 1
+        );
+        assert_eq!(
+            iso_week_end(2020, 53),
+            days_from_civil(2021, 1, 4) * 86_400 - 1
+        );
+        // 2025-W01 starts in the previous calendar year, on 2024-12-30.
+        assert_eq!(
+            iso_week_end(2025, 1),
+            days_from_civil(2025, 1, 6) * 86_400 - 1
+        );
+    }
+
+    #[test]
+    fn test_parse_iso_week() {
+        assert_eq!(parse_iso_week("2024-W07"), Some((2024, 7)));
+        assert_eq!(parse_iso_week("2020-W53"), Some((2020, 53)));
+        assert_eq!(parse_iso_week("2023-W53"), None);
+        assert_eq!(parse_iso_week("2024-W00"), None);
+        assert_eq!(parse_iso_week("2024-W7"), None);
+        assert_eq!(parse_iso_week("2024W07"), None);
+        assert_eq!(parse_iso_week("24-W07"), None);
+        assert_eq!(parse_iso_week("2024-W+7"), None);
+    }
+
+    #[test]
+    fn test_epoch_cutoff() {
+        let cutoff = parse_cutoff("@1700000000", false, &ctx(WeekStart::Monday)).unwrap();
+        assert_eq!(cutoff, Cutoff::At(1_700_000_000));
+        assert_eq!(cutoff.git_before(), "@1700000000");
+    }
+
+    #[test]
+    fn test_epoch_cutoff_rejects_non_numeric() {
+        let err = parse_cutoff("@abc", false, &ctx(WeekStart::Monday)).unwrap_err();
+        assert_eq!(err.to_string(), messages::invalid_epoch("@abc"));
+        assert!(parse_cutoff("@", true, &ctx(WeekStart::Monday)).is_err());
+    }
+
+    #[test]
+    fn test_git_before_forms() {
+        assert_eq!(Cutoff::Ago("2 days".into()).git_before(), "2 days ago");
+        assert_eq!(
+            Cutoff::Exact("2024-01-31".into()).git_before(),
+            "2024-01-31"
+        );
+        assert_eq!(Cutoff::At(1_700_000_000).git_before(), "@1700000000");
+    }
+}
@@ -0,0 +1,74 @@
+use crate::runner::{GitRunner, to_args};
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// First executable named `name` in the directories of a `PATH`-style list.
+pub fn find_on_path(name: &str, path_var: &OsStr) -> Option<PathBuf> {
+    env::split_paths(path_var)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Output of `git --version`, or `None` when git can't be run.
+pub fn git_version(runner: &dyn GitRunner) -> Option<String> {
+    let output = runner.git(&to_args(&["--version"])).ok()?;
+
+    output
+        .success
+        .then(|| output.stdout.trim().to_string())
+        .filter(|version| !version.is_empty())
+}
+
+/// Multi-line version report for bug reports: this tool, then git.
+pub fn report(tool_version: &str, git_version: Option<&str>, git_path: Option<&Path>) -> String {
+    let mut lines = vec![format!("{} {tool_version}", env!("CARGO_BIN_NAME"))];
+
+    match git_version {
+        Some(version) => lines.push(format!("git: {version}")),
+        None => lines.push("git: not found".to_string()),
+    }
+
+    if let Some(path) = git_path {
+        lines.push(format!("git path: {}", path.display()));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::GitOutput;
+    use crate::runner::mock::MockRunner;
+
+    #[test]
+    fn test_report_with_git() {
+        let runner = MockRunner::new().on(&["--version"], GitOutput::ok("git version 2.43.0\n"));
+        let version = git_version(&runner);
+
+        assert_eq!(
+            report("1.2.3", version.as_deref(), Some(Path::new("/usr/bin/git"))),
+            format!(
+                "{} 1.2.3\ngit: git version 2.43.0\ngit path: /usr/bin/git",
+                env!("CARGO_BIN_NAME")
+            )
+        );
+    }
+
+    #[test]
+    fn test_report_without_git() {
+        let runner = MockRunner::new();
+
+        assert_eq!(git_version(&runner), None);
+        assert_eq!(
+            report("1.2.3", None, None),
+            format!("{} 1.2.3\ngit: not found", env!("CARGO_BIN_NAME"))
+        );
+    }
+
+    #[test]
+    fn test_find_on_path_skips_missing_dirs() {
+        assert_eq!(find_on_path("git", OsStr::new("/nonexistent/bin")), None);
+    }
+}